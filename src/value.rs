@@ -1,9 +1,217 @@
 use std::collections::HashMap;
-use std::hash::Hash;
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops;
+use std::slice;
+use std::vec;
 
 use parser::Spanning;
 use ast::{InputValue, ToInputValue};
 
+#[cfg(feature = "serde")]
+use serde::de;
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+/// A signed or unsigned 64-bit integer, or a 64-bit float.
+#[derive(Clone, Debug)]
+pub struct Number {
+    n: N,
+}
+
+#[derive(Clone, Debug)]
+enum N {
+    PosInt(u64),
+    NegInt(i64),
+    Float(f64),
+    /// A decimal string, for integers too large for `u64`/`i64`.
+    #[cfg(feature = "arbitrary_precision")]
+    Precise(String),
+}
+
+/// The map key used to smuggle an arbitrary-precision number through the
+/// serde data model as a single-entry map, so it round-trips back into a
+/// `Number::Precise` instead of being mistaken for a `Value::String`. See
+/// the `Serialize`/`Deserialize` impls below.
+#[cfg(feature = "arbitrary_precision")]
+const NUMBER_TOKEN: &'static str = "$juniper::private::Number";
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        match (&self.n, &other.n) {
+            (&N::PosInt(a), &N::PosInt(b)) => a == b,
+            (&N::NegInt(a), &N::NegInt(b)) => a == b,
+            (&N::Float(a), &N::Float(b)) => a == b,
+            #[cfg(feature = "arbitrary_precision")]
+            (&N::Precise(ref a), &N::Precise(ref b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Number {
+    /// Is this number representable as an `i64`?
+    pub fn is_i64(&self) -> bool {
+        match self.n {
+            N::NegInt(_) => true,
+            N::PosInt(v) => v <= i64::max_value() as u64,
+            _ => false,
+        }
+    }
+
+    /// Is this number representable as a `u64`?
+    pub fn is_u64(&self) -> bool {
+        match self.n {
+            N::PosInt(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Is this number a float?
+    pub fn is_f64(&self) -> bool {
+        match self.n {
+            N::Float(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Is this number an integer, as opposed to a float?
+    pub fn is_integer(&self) -> bool {
+        match self.n {
+            N::Float(_) => false,
+            _ => true,
+        }
+    }
+
+    /// View this number as an `i64`, if it fits.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.n {
+            N::PosInt(n) => {
+                if n <= i64::max_value() as u64 { Some(n as i64) } else { None }
+            }
+            N::NegInt(n) => Some(n),
+            N::Float(_) => None,
+            #[cfg(feature = "arbitrary_precision")]
+            N::Precise(_) => None,
+        }
+    }
+
+    /// View this number as a `u64`, if it fits.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.n {
+            N::PosInt(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// View this number as an `f64`. Always succeeds for integers, widening
+    /// them as necessary.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.n {
+            N::PosInt(n) => Some(n as f64),
+            N::NegInt(n) => Some(n as f64),
+            N::Float(n) => Some(n),
+            #[cfg(feature = "arbitrary_precision")]
+            N::Precise(ref s) => s.parse().ok(),
+        }
+    }
+
+    /// Convert to an `i32`, saturating to `i32::MIN`/`i32::MAX` if the value
+    /// doesn't fit.
+    pub fn as_i32_saturating(&self) -> i32 {
+        match self.n {
+            N::PosInt(v) => {
+                if v > i32::max_value() as u64 { i32::max_value() } else { v as i32 }
+            }
+            N::NegInt(v) => {
+                if v < i32::min_value() as i64 { i32::min_value() } else { v as i32 }
+            }
+            N::Float(f) => {
+                if f > i32::max_value() as f64 {
+                    i32::max_value()
+                } else if f < i32::min_value() as f64 {
+                    i32::min_value()
+                } else {
+                    f as i32
+                }
+            }
+            #[cfg(feature = "arbitrary_precision")]
+            N::Precise(ref s) => {
+                if s.starts_with('-') { i32::min_value() } else { i32::max_value() }
+            }
+        }
+    }
+
+    /// Construct a `Number` holding a float. Accepts `NaN` and infinities
+    /// unvalidated, matching the historical behavior of `Value::float`.
+    pub fn from_f64(f: f64) -> Number {
+        Number { n: N::Float(f) }
+    }
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl Number {
+    /// Construct a `Number` from an arbitrary-precision decimal string, for
+    /// integers too large to fit in a `u64`/`i64`. The string is stored
+    /// as-is and is not validated.
+    pub fn from_string_unchecked(s: String) -> Number {
+        Number { n: N::Precise(s) }
+    }
+
+    /// The arbitrary-precision decimal string, if this `Number` was built
+    /// with `from_string_unchecked`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self.n {
+            N::Precise(ref s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.n {
+            N::PosInt(u) => fmt::Display::fmt(&u, f),
+            N::NegInt(i) => fmt::Display::fmt(&i, f),
+            N::Float(x) => fmt::Display::fmt(&x, f),
+            #[cfg(feature = "arbitrary_precision")]
+            N::Precise(ref s) => fmt::Display::fmt(s, f),
+        }
+    }
+}
+
+macro_rules! impl_number_from_unsigned {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(u: $ty) -> Self {
+                    Number { n: N::PosInt(u as u64) }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_number_from_signed {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(i: $ty) -> Self {
+                    if i < 0 {
+                        Number { n: N::NegInt(i as i64) }
+                    } else {
+                        Number { n: N::PosInt(i as u64) }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_number_from_unsigned!(u8, u16, u32, u64, usize);
+impl_number_from_signed!(i8, i16, i32, i64, isize);
+
 /// Serializable value returned from query and field execution.
 ///
 /// Used by the execution engine and resolvers to build up the response
@@ -17,12 +225,12 @@ use ast::{InputValue, ToInputValue};
 #[allow(missing_docs)]
 pub enum Value {
     Null,
-    Int(i32),
-    Float(f64),
+    Int(Number),
+    Float(Number),
     String(String),
     Boolean(bool),
     List(Vec<Value>),
-    Object(HashMap<String, Value>),
+    Object(Object),
 }
 
 impl Value {
@@ -32,10 +240,10 @@ impl Value {
     pub fn null() -> Value { Value::Null }
 
     /// Construct an integer value.
-    pub fn int(i: i32) -> Value { Value::Int(i) }
+    pub fn int(i: i32) -> Value { Value::Int(Number::from(i)) }
 
     /// Construct a floating point value.
-    pub fn float(f: f64) -> Value { Value::Float(f) }
+    pub fn float(f: f64) -> Value { Value::Float(Number::from_f64(f)) }
 
     /// Construct a string value.
     pub fn string<T: AsRef<str>>(s: T) -> Value { Value::String(s.as_ref().to_owned()) }
@@ -46,13 +254,13 @@ impl Value {
     /// Construct a list value.
     pub fn list(l: Vec<Value>) -> Value { Value::List(l) }
 
-    /// Construct an object value.
-    pub fn object<K>(o: HashMap<K, Value>) -> Value
-        where K: AsRef<str> + Eq + Hash
+    /// Construct an object value, preserving the order in which `o` yields
+    /// its entries.
+    pub fn object<K, I>(o: I) -> Value
+        where K: AsRef<str>,
+              I: IntoIterator<Item = (K, Value)>,
     {
-        Value::Object(
-            o.into_iter().map(|(k, v)| (k.as_ref().to_owned(), v)).collect()
-        )
+        Value::Object(o.into_iter().collect())
     }
 
     // DISCRIMINATORS
@@ -66,7 +274,7 @@ impl Value {
     }
 
     /// View the underlying object value, if present.
-    pub fn as_object_value(&self) -> Option<&HashMap<String, Value>> {
+    pub fn as_object_value(&self) -> Option<&Object> {
         match *self {
             Value::Object(ref o) => Some(o),
             _ => None,
@@ -74,7 +282,7 @@ impl Value {
     }
 
     /// Mutable view into the underlying object value, if present.
-    pub fn as_mut_object_value(&mut self) -> Option<&mut HashMap<String, Value>> {
+    pub fn as_mut_object_value(&mut self) -> Option<&mut Object> {
         match *self {
             Value::Object(ref mut o) => Some(o),
             _ => None,
@@ -96,14 +304,26 @@ impl Value {
             _ => None,
         }
     }
+
+    /// Look up an object key or list index, returning `None` if this isn't
+    /// the right kind of value, the key is missing, or the index is out of
+    /// range - unlike the `Index` operator, which returns `Value::Null` in
+    /// those cases.
+    pub fn get<I: Index>(&self, index: I) -> Option<&Value> {
+        index.index_into(self)
+    }
 }
 
 impl ToInputValue for Value {
     fn to(&self) -> InputValue {
         match *self {
             Value::Null => InputValue::Null,
-            Value::Int(i) => InputValue::Int(i),
-            Value::Float(f) => InputValue::Float(f),
+            // `ast::InputValue::Int` is still a 32-bit primitive, so a
+            // `Number` outside of `i32` range is saturated rather than
+            // wrapped - the precision widening only reaches as far as
+            // `Value` itself.
+            Value::Int(ref n) => InputValue::Int(n.as_i32_saturating()),
+            Value::Float(ref n) => InputValue::Float(n.as_f64().unwrap_or_default()),
             Value::String(ref s) => InputValue::String(s.clone()),
             Value::Boolean(b) => InputValue::Boolean(b),
             Value::List(ref l) => InputValue::List(l.iter().map(|x|
@@ -113,3 +333,776 @@ impl ToInputValue for Value {
         }
     }
 }
+
+#[cfg(feature = "serde")]
+impl Serialize for Number {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.n {
+            N::PosInt(u) => serializer.serialize_u64(u),
+            N::NegInt(i) => serializer.serialize_i64(i),
+            N::Float(f) => serializer.serialize_f64(f),
+            // Emit as a single-entry `{ NUMBER_TOKEN: "<digits>" }` map
+            // rather than a bare string, so `Value`'s `Deserialize` impl can
+            // tell it apart from an actual `Value::String` and reconstruct a
+            // `Number::Precise` instead.
+            #[cfg(feature = "arbitrary_precision")]
+            N::Precise(ref s) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(NUMBER_TOKEN, s)?;
+                map.end()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Int(ref n) => n.serialize(serializer),
+            Value::Float(ref n) => n.serialize(serializer),
+            Value::String(ref s) => serializer.serialize_str(s),
+            Value::Boolean(b) => serializer.serialize_bool(b),
+            Value::List(ref l) => {
+                let mut seq = serializer.serialize_seq(Some(l.len()))?;
+                for value in l {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Value::Object(ref o) => {
+                let mut map = serializer.serialize_map(Some(o.len()))?;
+                for (k, v) in o {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> de::Deserialize<'de> for Value {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+
+        impl<'de> de::Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                formatter.write_str("a valid GraphQL response value")
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+                Ok(Value::null())
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+                Ok(Value::null())
+            }
+
+            fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+                Ok(Value::boolean(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+                Ok(Value::Int(Number::from(v)))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+                Ok(Value::Int(Number::from(v)))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+                Ok(Value::Float(Number::from_f64(v)))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+                Ok(Value::string(v))
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+                Ok(Value::String(v))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+                let mut list = match seq.size_hint() {
+                    Some(size) => Vec::with_capacity(size),
+                    None => Vec::new(),
+                };
+                while let Some(value) = seq.next_element()? {
+                    list.push(value);
+                }
+                Ok(Value::list(list))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+                let mut object = match map.size_hint() {
+                    Some(size) => Object::with_capacity(size),
+                    None => Object::new(),
+                };
+
+                #[cfg(feature = "arbitrary_precision")]
+                {
+                    if let Some(first_key) = map.next_key::<String>()? {
+                        if first_key == NUMBER_TOKEN {
+                            let raw: String = map.next_value()?;
+                            return Ok(Value::Int(Number::from_string_unchecked(raw)));
+                        }
+                        let first_value = map.next_value()?;
+                        object.insert(first_key, first_value);
+                    }
+                }
+
+                while let Some((key, value)) = map.next_entry::<String, Value>()? {
+                    object.insert(key, value);
+                }
+                Ok(Value::Object(object))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// An order-preserving string-keyed map of `Value`s, used by `Value::Object`.
+#[derive(Debug, PartialEq, Default)]
+pub struct Object {
+    key_to_index: HashMap<String, usize>,
+    entries: Vec<(String, Value)>,
+}
+
+impl Object {
+    /// Create a new, empty object.
+    pub fn new() -> Self {
+        Object {
+            key_to_index: HashMap::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Create a new, empty object with room for at least `capacity` entries
+    /// without reallocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Object {
+            key_to_index: HashMap::with_capacity(capacity),
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Insert a value under `key`, returning the previous value if the key
+    /// was already present.
+    ///
+    /// Inserting a new key appends it to the end of the iteration order.
+    /// Overwriting an existing key updates its value in place, leaving its
+    /// position in the iteration order unchanged.
+    pub fn insert<K: AsRef<str>>(&mut self, key: K, value: Value) -> Option<Value> {
+        let key = key.as_ref();
+        match self.key_to_index.get(key).cloned() {
+            Some(index) => Some(mem::replace(&mut self.entries[index].1, value)),
+            None => {
+                self.key_to_index.insert(key.to_owned(), self.entries.len());
+                self.entries.push((key.to_owned(), value));
+                None
+            }
+        }
+    }
+
+    /// Get a reference to the value keyed by `key`, if present.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.key_to_index.get(key).map(|&index| &self.entries[index].1)
+    }
+
+    /// Get a mutable reference to the value keyed by `key`, if present.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        match self.key_to_index.get(key) {
+            Some(&index) => Some(&mut self.entries[index].1),
+            None => None,
+        }
+    }
+
+    /// Does the object contain a value keyed by `key`?
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.key_to_index.contains_key(key)
+    }
+
+    /// The number of entries in the object.
+    pub fn len(&self) -> usize { self.entries.len() }
+
+    /// Is the object empty?
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+    /// Iterate over the entries of the object, in insertion order.
+    pub fn iter(&self) -> Iter {
+        Iter { iter: self.entries.iter() }
+    }
+
+    /// Mutably iterate over the entries of the object, in insertion order.
+    pub fn iter_mut(&mut self) -> IterMut {
+        IterMut { iter: self.entries.iter_mut() }
+    }
+}
+
+impl<K: AsRef<str>> FromIterator<(K, Value)> for Object {
+    fn from_iter<I: IntoIterator<Item = (K, Value)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut object = Object::with_capacity(iter.size_hint().0);
+        for (k, v) in iter {
+            object.insert(k, v);
+        }
+        object
+    }
+}
+
+impl IntoIterator for Object {
+    type Item = (String, Value);
+    type IntoIter = vec::IntoIter<(String, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Object {
+    type Item = (&'a String, &'a Value);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+/// An iterator over the entries of an `Object`, in insertion order. See
+/// [`Object::iter`](struct.Object.html#method.iter).
+pub struct Iter<'a> {
+    iter: slice::Iter<'a, (String, Value)>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a String, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|&(ref k, ref v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) { self.iter.size_hint() }
+}
+
+/// A mutable iterator over the entries of an `Object`, in insertion order.
+/// See [`Object::iter_mut`](struct.Object.html#method.iter_mut).
+pub struct IterMut<'a> {
+    iter: slice::IterMut<'a, (String, Value)>,
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a String, &'a mut Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|&mut (ref k, ref mut v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) { self.iter.size_hint() }
+}
+
+impl From<i32> for Value {
+    fn from(i: i32) -> Self { Value::int(i) }
+}
+
+impl From<f64> for Value {
+    fn from(f: f64) -> Self { Value::float(f) }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self { Value::boolean(b) }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self { Value::String(s) }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(s: &'a str) -> Self { Value::string(s) }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(l: Vec<Value>) -> Self { Value::list(l) }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        match v {
+            Some(v) => v.into(),
+            None => Value::Null,
+        }
+    }
+}
+
+/// A type that can be used to index into a `Value`: either a string-like key
+/// into a `Value::Object`, or an integer index into a `Value::List`.
+///
+/// This trait is sealed and not meant to be implemented outside of this
+/// crate. See the `Index` operator impl on `Value` and
+/// [`Value::get`](enum.Value.html#method.get).
+pub trait Index: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value>;
+}
+
+impl Index for usize {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        match *v {
+            Value::List(ref list) => list.get(*self),
+            _ => None,
+        }
+    }
+}
+
+impl Index for str {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        match *v {
+            Value::Object(ref object) => object.get(self),
+            _ => None,
+        }
+    }
+}
+
+impl Index for String {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        self.as_str().index_into(v)
+    }
+}
+
+impl<'a, T: ?Sized + Index> Index for &'a T {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(v)
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for String {}
+    impl<'a, T: ?Sized + Sealed> Sealed for &'a T {}
+}
+
+/// The `Value::Null` returned for missing keys or out-of-range indices by
+/// the `Index` operator impl below.
+static NULL: Value = Value::Null;
+
+impl<I: Index> ops::Index<I> for Value {
+    type Output = Value;
+
+    /// Index into a `juniper::Value` using the syntax `value[0]` or
+    /// `value["key"]`.
+    ///
+    /// Returns `Value::Null` if the type doesn't match (e.g. indexing a
+    /// `Value::List` with a string), the key is missing, or the index is out
+    /// of range, mirroring the behavior of a GraphQL field that resolved to
+    /// `null`. Use [`Value::get`](enum.Value.html#method.get) if you need to
+    /// tell "absent" apart from an actual `null`.
+    fn index(&self, index: I) -> &Value {
+        index.index_into(self).unwrap_or(&NULL)
+    }
+}
+
+/// Build a `Value` from near-literal syntax.
+///
+/// ```rust
+/// # #[macro_use] extern crate juniper;
+/// # fn main() {
+/// let user_id = 43;
+///
+/// let value = graphql_value!({
+///     "name": "John",
+///     "age": user_id,
+///     "phones": [
+///         "+44 1234567",
+///         "+44 2345678"
+///     ]
+/// });
+/// # }
+/// ```
+///
+/// Rust expressions can be interpolated, and `null` maps to `Value::Null`:
+///
+/// ```rust
+/// # #[macro_use] extern crate juniper;
+/// # use juniper::Value;
+/// # fn main() {
+/// assert_eq!(graphql_value!(null), Value::null());
+/// assert_eq!(graphql_value!([1, 2, 3]), Value::list(vec![
+///     Value::int(1), Value::int(2), Value::int(3),
+/// ]));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! graphql_value {
+    ///////////////////////////////////////////////////////////////////////
+    // TT muncher for the inside of an object `{ .. }`. Each entry is
+    // inserted into the given map variable.
+    //
+    // Must be invoked as: graphql_value!(@object $map () ($($tt)*) ($($tt)*))
+    ///////////////////////////////////////////////////////////////////////
+
+    (@object $map:ident () () ()) => {};
+
+    // Insert the current entry followed by a trailing comma.
+    (@object $map:ident [$($key:tt)+] ($value:expr) , $($rest:tt)*) => {
+        let _ = $map.insert($($key)+, $value);
+        graphql_value!(@object $map () ($($rest)*) ($($rest)*));
+    };
+
+    // Insert the last entry without a trailing comma.
+    (@object $map:ident [$($key:tt)+] ($value:expr)) => {
+        let _ = $map.insert($($key)+, $value);
+    };
+
+    // Next value is `null`.
+    (@object $map:ident ($($key:tt)+) (: null $($rest:tt)*) $copy:tt) => {
+        graphql_value!(@object $map [$($key)+] ($crate::Value::null()) $($rest)*);
+    };
+
+    // Next value is an array.
+    (@object $map:ident ($($key:tt)+) (: [$($array:tt)*] $($rest:tt)*) $copy:tt) => {
+        graphql_value!(@object $map [$($key)+] (graphql_value!([$($array)*])) $($rest)*);
+    };
+
+    // Next value is a nested object.
+    (@object $map:ident ($($key:tt)+) (: {$($nested:tt)*} $($rest:tt)*) $copy:tt) => {
+        graphql_value!(@object $map [$($key)+] (graphql_value!({$($nested)*})) $($rest)*);
+    };
+
+    // Next value is an expression followed by a comma.
+    (@object $map:ident ($($key:tt)+) (: $value:expr , $($rest:tt)*) $copy:tt) => {
+        graphql_value!(@object $map [$($key)+] (::std::convert::Into::<$crate::Value>::into($value)) , $($rest)*);
+    };
+
+    // Last value is an expression with no trailing comma.
+    (@object $map:ident ($($key:tt)+) (: $value:expr) $copy:tt) => {
+        graphql_value!(@object $map [$($key)+] (::std::convert::Into::<$crate::Value>::into($value)));
+    };
+
+    // Munch a token into the current key.
+    (@object $map:ident ($($key:tt)*) ($tt:tt $($rest:tt)*) $copy:tt) => {
+        graphql_value!(@object $map ($($key)* $tt) ($($rest)*) ($($rest)*));
+    };
+
+    ///////////////////////////////////////////////////////////////////////
+    // TT muncher for the inside of an array `[ .. ]`. Builds up a `Vec` of
+    // the elements.
+    //
+    // Must be invoked as: graphql_value!(@array [] $($tt)*)
+    ///////////////////////////////////////////////////////////////////////
+
+    // Done with a trailing comma.
+    (@array [$($elems:expr,)*]) => {
+        vec![$($elems,)*]
+    };
+
+    // Done without a trailing comma.
+    (@array [$($elems:expr),*]) => {
+        vec![$($elems),*]
+    };
+
+    // Next element is `null`.
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        graphql_value!(@array [$($elems,)* $crate::Value::null()] $($rest)*)
+    };
+
+    // Next element is an array.
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        graphql_value!(@array [$($elems,)* graphql_value!([$($array)*])] $($rest)*)
+    };
+
+    // Next element is an object.
+    (@array [$($elems:expr,)*] {$($object:tt)*} $($rest:tt)*) => {
+        graphql_value!(@array [$($elems,)* graphql_value!({$($object)*})] $($rest)*)
+    };
+
+    // Next element is an expression followed by a comma.
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        graphql_value!(@array [$($elems,)* ::std::convert::Into::<$crate::Value>::into($next),] $($rest)*)
+    };
+
+    // Last element is an expression with no trailing comma.
+    (@array [$($elems:expr,)*] $last:expr) => {
+        graphql_value!(@array [$($elems,)* ::std::convert::Into::<$crate::Value>::into($last)])
+    };
+
+    // Comma after the most recent element.
+    (@array [$($elems:expr),*] , $($rest:tt)*) => {
+        graphql_value!(@array [$($elems,)*] $($rest)*)
+    };
+
+    // Unexpected token after most recent element.
+    (@array [$($elems:expr),*] $unexpected:tt $($rest:tt)*) => {
+        graphql_value_unexpected!($unexpected)
+    };
+
+    ///////////////////////////////////////////////////////////////////////
+    // The main entry points.
+    ///////////////////////////////////////////////////////////////////////
+
+    (null) => {
+        $crate::Value::null()
+    };
+
+    ([]) => {
+        $crate::Value::list(Vec::new())
+    };
+
+    ([ $($tt:tt)+ ]) => {
+        $crate::Value::list(graphql_value!(@array [] $($tt)+))
+    };
+
+    ({}) => {
+        $crate::Value::Object($crate::value::Object::new())
+    };
+
+    ({ $($tt:tt)+ }) => {
+        $crate::Value::Object({
+            let mut object = $crate::value::Object::new();
+            graphql_value!(@object object () ($($tt)+) ($($tt)+));
+            object
+        })
+    };
+
+    // Any other expression: numbers, strings, variables, nested calls, ...
+    ($other:expr) => {
+        ::std::convert::Into::<$crate::Value>::into($other)
+    };
+}
+
+// Invoked with a single token that `graphql_value!`'s array muncher didn't
+// expect. Has no matching rule, so it surfaces a "no rules expected this
+// token" error pointing at the offending token instead of a confusing one
+// further up the call stack.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! graphql_value_unexpected {
+    () => {};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_insert_preserves_order() {
+        let mut object = Object::new();
+        object.insert("b", Value::int(2));
+        object.insert("a", Value::int(1));
+        let keys: Vec<&str> = object.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["b", "a"]);
+    }
+
+    #[test]
+    fn object_insert_overwrite_keeps_position() {
+        let mut object = Object::new();
+        object.insert("a", Value::int(1));
+        object.insert("b", Value::int(2));
+        let old = object.insert("a", Value::int(3));
+        assert_eq!(old, Some(Value::int(1)));
+        let keys: Vec<&str> = object.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["a", "b"]);
+        assert_eq!(object.get("a"), Some(&Value::int(3)));
+    }
+
+    #[test]
+    fn object_get_missing_key() {
+        let object = Object::new();
+        assert_eq!(object.get("missing"), None);
+        assert!(!object.contains_key("missing"));
+    }
+
+    #[test]
+    fn object_into_iter_preserves_order() {
+        let mut object = Object::new();
+        object.insert("first", Value::int(1));
+        object.insert("second", Value::int(2));
+        let entries: Vec<(String, Value)> = object.into_iter().collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("first".to_owned(), Value::int(1)),
+                ("second".to_owned(), Value::int(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn graphql_value_null() {
+        assert_eq!(graphql_value!(null), Value::null());
+    }
+
+    #[test]
+    fn graphql_value_scalars() {
+        assert_eq!(graphql_value!(1), Value::int(1));
+        assert_eq!(graphql_value!(1.5), Value::float(1.5));
+        assert_eq!(graphql_value!(true), Value::boolean(true));
+        assert_eq!(graphql_value!("a"), Value::string("a"));
+    }
+
+    #[test]
+    fn graphql_value_list() {
+        assert_eq!(
+            graphql_value!([1, 2, 3]),
+            Value::list(vec![Value::int(1), Value::int(2), Value::int(3)])
+        );
+        assert_eq!(graphql_value!([]), Value::list(vec![]));
+        assert_eq!(
+            graphql_value!([1, null, [2]]),
+            Value::list(vec![
+                Value::int(1),
+                Value::null(),
+                Value::list(vec![Value::int(2)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn graphql_value_object() {
+        let value = graphql_value!({
+            "name": "John",
+            "age": 43,
+            "is_cool": null,
+        });
+        let object = value.as_object_value().unwrap();
+        assert_eq!(object.get("name"), Some(&Value::string("John")));
+        assert_eq!(object.get("age"), Some(&Value::int(43)));
+        assert_eq!(object.get("is_cool"), Some(&Value::null()));
+    }
+
+    #[test]
+    fn graphql_value_object_no_trailing_comma() {
+        let value = graphql_value!({ "a": 1, "b": 2 });
+        let object = value.as_object_value().unwrap();
+        assert_eq!(object.get("a"), Some(&Value::int(1)));
+        assert_eq!(object.get("b"), Some(&Value::int(2)));
+    }
+
+    #[test]
+    fn graphql_value_nested_object() {
+        let value = graphql_value!({
+            "id": 1,
+            "phones": ["a", "b"],
+            "address": { "city": "NYC" },
+        });
+        let object = value.as_object_value().unwrap();
+        assert_eq!(
+            object.get("phones"),
+            Some(&Value::list(vec![Value::string("a"), Value::string("b")]))
+        );
+        let address = object.get("address").unwrap().as_object_value().unwrap();
+        assert_eq!(address.get("city"), Some(&Value::string("NYC")));
+    }
+
+    #[test]
+    fn graphql_value_interpolates_expressions() {
+        let user_id = 43;
+        assert_eq!(graphql_value!(user_id), Value::int(43));
+        assert_eq!(
+            graphql_value!({ "id": user_id + 1 }).as_object_value().unwrap().get("id"),
+            Some(&Value::int(44))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn value_serde_round_trip() {
+        let value = graphql_value!({ "name": "John", "phones": ["a", "b"] });
+        let json = ::serde_json::to_string(&value).unwrap();
+        let round_tripped: Value = ::serde_json::from_str(&json).unwrap();
+        assert_eq!(value, round_tripped);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn value_serializes_object_in_insertion_order() {
+        let value = graphql_value!({ "b": 1, "a": 2 });
+        let json = ::serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"b":1,"a":2}"#);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn value_deserializes_null() {
+        let value: Value = ::serde_json::from_str("null").unwrap();
+        assert_eq!(value, Value::null());
+    }
+
+    #[test]
+    fn from_conversions() {
+        assert_eq!(Value::from(1), Value::int(1));
+        assert_eq!(Value::from(1.5), Value::float(1.5));
+        assert_eq!(Value::from(true), Value::boolean(true));
+        assert_eq!(Value::from("a"), Value::string("a"));
+        assert_eq!(Value::from("a".to_owned()), Value::string("a"));
+        assert_eq!(Value::from(vec![Value::int(1)]), Value::list(vec![Value::int(1)]));
+        assert_eq!(Value::from(None::<i32>), Value::null());
+        assert_eq!(Value::from(Some(1)), Value::int(1));
+    }
+
+    #[test]
+    fn index_into_list() {
+        let value = graphql_value!([1, 2, 3]);
+        assert_eq!(value[0], Value::int(1));
+        assert_eq!(value[2], Value::int(3));
+        assert_eq!(value[99], Value::null());
+        assert_eq!(value.get(1), Some(&Value::int(2)));
+        assert_eq!(value.get(99), None);
+    }
+
+    #[test]
+    fn index_into_object() {
+        let value = graphql_value!({ "name": "John" });
+        assert_eq!(value["name"], Value::string("John"));
+        assert_eq!(value["missing"], Value::null());
+        assert_eq!(value.get("name"), Some(&Value::string("John")));
+        assert_eq!(value.get("missing"), None);
+    }
+
+    #[test]
+    fn index_wrong_kind_returns_null() {
+        let value = graphql_value!([1, 2, 3]);
+        assert_eq!(value["name"], Value::null());
+        assert_eq!(value.get("name"), None);
+    }
+
+    #[test]
+    fn number_kind_predicates() {
+        assert!(Number::from(1u32).is_u64());
+        assert!(Number::from(1u32).is_i64());
+        assert!(Number::from(-1i32).is_i64());
+        assert!(!Number::from(-1i32).is_u64());
+        assert!(Number::from_f64(1.5).is_f64());
+        assert!(!Number::from_f64(1.5).is_integer());
+        assert!(Number::from(1u32).is_integer());
+    }
+
+    #[test]
+    fn number_as_conversions() {
+        assert_eq!(Number::from(1u32).as_u64(), Some(1));
+        assert_eq!(Number::from(1u32).as_i64(), Some(1));
+        assert_eq!(Number::from(-1i32).as_u64(), None);
+        assert_eq!(Number::from_f64(1.5).as_f64(), Some(1.5));
+        assert_eq!(Number::from(1u32).as_f64(), Some(1.0));
+    }
+
+    #[test]
+    fn number_as_i32_saturating() {
+        assert_eq!(Number::from(1i32).as_i32_saturating(), 1);
+        assert_eq!(Number::from(5_000_000_000i64).as_i32_saturating(), i32::max_value());
+        assert_eq!(Number::from(-5_000_000_000i64).as_i32_saturating(), i32::min_value());
+        assert_eq!(Number::from(u64::max_value()).as_i32_saturating(), i32::max_value());
+    }
+
+    #[cfg(all(feature = "serde", feature = "arbitrary_precision"))]
+    #[test]
+    fn number_precise_round_trip() {
+        let value = Value::Int(Number::from_string_unchecked("123456789012345678901234567890".to_owned()));
+        let json = ::serde_json::to_string(&value).unwrap();
+        let round_tripped: Value = ::serde_json::from_str(&json).unwrap();
+        assert_eq!(value, round_tripped);
+    }
+}